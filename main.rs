@@ -1,11 +1,14 @@
+use std::collections::VecDeque;
+use std::fs;
 use std::io;
 use std::time::{Duration, Instant};
 
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use crossterm::terminal;
 
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use ratatui::widgets::Wrap;
 use ratatui::{
     DefaultTerminal, Frame,
@@ -19,14 +22,35 @@ use ratatui::{
 // Constants used for grid sizes
 const WORLD_W: usize = 80;
 const WORLD_H: usize = 42;
-// Tick duration in ms
+// Default tick duration; App.tick starts here and is adjustable at runtime
 const TICK: Duration = Duration::from_millis(50);
+// Bounds and step size for the +/- speed controls, in ms
+const MIN_TICK_MS: u64 = 5;
+const MAX_TICK_MS: u64 = 500;
+const TICK_STEP_MS: u64 = 10;
+// Number of step_active iterations run per tick while turbo is on
+const TURBO_STEPS: u32 = 10;
 // Constants used for the layout
 const DEBUG_COLS: u16 = 30;
 // Constant used for DrunkWalk Gen
 const DRUNKCHANCE: f64 = 0.4;
+// Number of independent walkers spawned by the DrunkWalk carver
+const DRUNK_AGENTS: usize = 4;
+// Above this many regions, `connect_regions` skips tunnel carving (an
+// O(n^2) Prim's pass) and just keeps the largest region, since very noisy
+// grids can have thousands of tiny regions
+const REGION_CONNECT_THRESHOLD: usize = 200;
+// File a map is saved to / loaded from with `w` / `l`
+const SAVE_PATH: &str = "cave_save.json";
+// Smallest w/h a loaded map may declare; anything smaller can't hold the
+// 1-cell border the generators and clamping logic assume
+const MIN_MAP_DIM: usize = 3;
 // Constant used for Random Gen
 const RANDCHANCE: f64 = 0.45;
+// Constant used to seed a fresh Smooth run with noise before smoothing it
+const SMOOTHCHANCE: f64 = 0.45;
+// Max number of operations kept on the undo stack
+const UNDO_CAP: usize = 256;
 
 fn main() -> io::Result<()> {
     ratatui::run(|terminal| App::default().run(terminal))
@@ -36,6 +60,7 @@ enum Algorithm {
     Paint,
     Life,
     DrunkWalk,
+    Smooth,
 }
 
 #[derive(Debug)]
@@ -53,6 +78,15 @@ pub struct App {
     algo: Algorithm,
     running: bool,
     last_tick: Instant,
+    undo_stack: UndoStack,
+    region_count: usize,
+    largest_region: usize,
+    tick: Duration,
+    turbo: bool,
+    status: String,
+    selecting: bool,
+    selection: Option<(usize, usize, usize, usize)>,
+    clipboard: Option<(usize, usize, Vec<bool>)>,
 }
 #[derive(Debug)]
 struct Grid {
@@ -62,6 +96,97 @@ struct Grid {
     next: Vec<bool>,
 }
 
+/// A single 4-connected region of open cells found by `Grid::label_regions`.
+#[derive(Debug, Clone, Copy)]
+struct RegionInfo {
+    count: usize,
+    rep: (usize, usize),
+}
+
+/// A single cell flip recorded as part of an `Operation`.
+#[derive(Debug, Clone, Copy)]
+struct ModifyRecord {
+    x: usize,
+    y: usize,
+    old: bool,
+    new: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OpKind {
+    Paint,
+    Fill,
+    Generate,
+}
+
+/// One undoable unit of work: all the cell flips a single edit produced.
+#[derive(Debug, Clone)]
+struct Operation {
+    kind: OpKind,
+    records: Vec<ModifyRecord>,
+}
+
+/// Classic undo/redo operation log for `Grid` edits.
+///
+/// Paint-mode toggles are coalesced into a single in-progress `Operation`
+/// while the user keeps pressing space, and get pushed onto the stack as
+/// soon as any other key ends the stroke. Destructive actions (clear,
+/// regen, drunk-walk carving) push one `Operation` snapshotting every
+/// cell they changed.
+#[derive(Debug, Default)]
+struct UndoStack {
+    past: Vec<Operation>,
+    future: Vec<Operation>,
+    in_progress: Option<Operation>,
+}
+
+impl UndoStack {
+    fn record_paint(&mut self, x: usize, y: usize, old: bool, new: bool) {
+        if old == new {
+            return;
+        }
+        let op = self.in_progress.get_or_insert_with(|| Operation {
+            kind: OpKind::Paint,
+            records: Vec::new(),
+        });
+        op.records.push(ModifyRecord { x, y, old, new });
+    }
+
+    fn end_stroke(&mut self) {
+        if let Some(op) = self.in_progress.take() {
+            self.push(op);
+        }
+    }
+
+    fn push(&mut self, op: Operation) {
+        if op.records.is_empty() {
+            return;
+        }
+        self.past.push(op);
+        if self.past.len() > UNDO_CAP {
+            self.past.remove(0);
+        }
+        self.future.clear();
+    }
+
+    fn undo(&mut self) -> Option<Operation> {
+        self.end_stroke();
+        let op = self.past.pop()?;
+        self.future.push(op.clone());
+        Some(op)
+    }
+
+    fn redo(&mut self) -> Option<Operation> {
+        let op = self.future.pop()?;
+        self.past.push(op.clone());
+        Some(op)
+    }
+
+    fn last_kind(&self) -> Option<OpKind> {
+        self.past.last().map(|op| op.kind)
+    }
+}
+
 impl Grid {
     fn new(w: usize, h: usize) -> Self {
         Self {
@@ -146,6 +271,229 @@ impl Grid {
 
         std::mem::swap(&mut self.cells, &mut self.next);
     }
+
+    /// Cellular-automata cave smoothing: counts walls in the 8-cell Moore
+    /// neighborhood (out-of-bounds counts as wall, keeping edges solid)
+    /// and applies the standard cave rule. Run repeatedly over random
+    /// noise it converges into organic cave chambers.
+    fn step_smooth(&mut self) {
+        for y in 0..self.h {
+            for x in 0..self.w {
+                let i = self.idx(x, y);
+
+                let mut walls: u8 = 0;
+
+                for dy in -1isize..=1 {
+                    for dx in -1isize..=1 {
+                        if dx == 0 && dy == 0 {
+                            continue;
+                        }
+
+                        let nx = x as isize + dx;
+                        let ny = y as isize + dy;
+
+                        let is_wall = if nx < 0 || ny < 0 {
+                            true
+                        } else {
+                            let nx = nx as usize;
+                            let ny = ny as usize;
+                            nx >= self.w || ny >= self.h || self.cells[self.idx(nx, ny)]
+                        };
+
+                        if is_wall {
+                            walls += 1;
+                        }
+                    }
+                }
+
+                self.next[i] = if walls >= 5 {
+                    true
+                } else if walls <= 3 {
+                    false
+                } else {
+                    self.cells[i]
+                };
+            }
+        }
+
+        std::mem::swap(&mut self.cells, &mut self.next);
+    }
+
+    /// Flood-fills every open cell into 4-connected regions, returning a
+    /// per-cell label buffer (`-1` for walls) and size/representative info
+    /// for each region found, in the order they were discovered.
+    fn label_regions(&self) -> (Vec<i32>, Vec<RegionInfo>) {
+        let mut region = vec![-1i32; self.cells.len()];
+        let mut regions: Vec<RegionInfo> = Vec::new();
+
+        for y in 0..self.h {
+            for x in 0..self.w {
+                let i = self.idx(x, y);
+                if self.cells[i] || region[i] != -1 {
+                    continue;
+                }
+
+                let id = regions.len() as i32;
+                region[i] = id;
+                let mut count = 0usize;
+                let mut queue = VecDeque::new();
+                queue.push_back((x, y));
+
+                while let Some((cx, cy)) = queue.pop_front() {
+                    count += 1;
+
+                    let neighbors = [
+                        (cx.wrapping_sub(1), cy),
+                        (cx + 1, cy),
+                        (cx, cy.wrapping_sub(1)),
+                        (cx, cy + 1),
+                    ];
+                    for (nx, ny) in neighbors {
+                        if !self.in_bounds(nx, ny) {
+                            continue;
+                        }
+                        let ni = self.idx(nx, ny);
+                        if !self.cells[ni] && region[ni] == -1 {
+                            region[ni] = id;
+                            queue.push_back((nx, ny));
+                        }
+                    }
+                }
+
+                regions.push(RegionInfo { count, rep: (x, y) });
+            }
+        }
+
+        (region, regions)
+    }
+
+    /// Walls off every region except the largest, given the label buffer
+    /// and region list from `label_regions`. Cheap O(cells) alternative
+    /// to tunnel carving when there are too many regions to connect.
+    fn keep_largest_region(&mut self, labels: &[i32], regions: &[RegionInfo]) {
+        let Some(largest_id) = regions
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, r)| r.count)
+            .map(|(id, _)| id as i32)
+        else {
+            return;
+        };
+
+        for (i, &label) in labels.iter().enumerate() {
+            if label != -1 && label != largest_id {
+                self.cells[i] = true;
+            }
+        }
+    }
+
+    /// Opens a single cell unless it sits on the 1-cell border wall.
+    fn carve_interior(&mut self, x: usize, y: usize) {
+        if x == 0 || y == 0 || x == self.w - 1 || y == self.h - 1 {
+            return;
+        }
+        self.set(x, y, false);
+    }
+
+    /// Carves a straight L-shaped tunnel between two representative
+    /// cells: horizontal first, then vertical, leaving the border intact.
+    fn carve_l_tunnel(&mut self, from: (usize, usize), to: (usize, usize)) {
+        let (fx, fy) = from;
+        let (tx, ty) = to;
+
+        let (x0, x1) = if fx < tx { (fx, tx) } else { (tx, fx) };
+        for x in x0..=x1 {
+            self.carve_interior(x, fy);
+        }
+
+        let (y0, y1) = if fy < ty { (fy, ty) } else { (ty, fy) };
+        for y in y0..=y1 {
+            self.carve_interior(tx, y);
+        }
+    }
+}
+
+fn manhattan(a: (usize, usize), b: (usize, usize)) -> usize {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+/// On-disk representation of a `Grid` snapshot: dimensions, seed, active
+/// algorithm, and the cells run-length-encoded so an 80x42 map stays a
+/// small, human-inspectable JSON file.
+#[derive(Debug, Serialize, Deserialize)]
+struct SaveFile {
+    w: usize,
+    h: usize,
+    seed: u64,
+    algo: String,
+    cells: String,
+}
+
+/// Run-length encodes a cell buffer as `<count><. or #>` pairs, e.g.
+/// `"80#1.78#"`, with `#` for wall and `.` for open.
+fn encode_cells(cells: &[bool]) -> String {
+    let mut out = String::new();
+    let mut iter = cells.iter();
+
+    if let Some(&first) = iter.next() {
+        let mut run_char = first;
+        let mut run_len = 1usize;
+
+        for &c in iter {
+            if c == run_char {
+                run_len += 1;
+            } else {
+                out.push_str(&run_len.to_string());
+                out.push(if run_char { '#' } else { '.' });
+                run_char = c;
+                run_len = 1;
+            }
+        }
+        out.push_str(&run_len.to_string());
+        out.push(if run_char { '#' } else { '.' });
+    }
+
+    out
+}
+
+/// Inverse of `encode_cells`, rejecting the result unless it decodes to
+/// exactly `expected_len` cells.
+fn decode_cells(s: &str, expected_len: usize) -> Option<Vec<bool>> {
+    let mut cells = Vec::with_capacity(expected_len);
+    let mut num = String::new();
+
+    for ch in s.chars() {
+        if ch.is_ascii_digit() {
+            num.push(ch);
+            continue;
+        }
+
+        let run: usize = num.parse().ok()?;
+        num.clear();
+
+        let val = match ch {
+            '#' => true,
+            '.' => false,
+            _ => return None,
+        };
+        cells.extend(std::iter::repeat_n(val, run));
+    }
+
+    (cells.len() == expected_len).then_some(cells)
+}
+
+fn algo_to_str(algo: &Algorithm) -> String {
+    format!("{algo:?}")
+}
+
+fn algo_from_str(s: &str) -> Option<Algorithm> {
+    match s {
+        "Paint" => Some(Algorithm::Paint),
+        "Life" => Some(Algorithm::Life),
+        "DrunkWalk" => Some(Algorithm::DrunkWalk),
+        "Smooth" => Some(Algorithm::Smooth),
+        _ => None,
+    }
 }
 
 impl App {
@@ -156,8 +504,11 @@ impl App {
             if event::poll(Duration::from_millis(50))? {
                 self.handle_events()?;
             }
-            if self.running && self.last_tick.elapsed() >= TICK {
-                self.step_active();
+            if self.running && self.last_tick.elapsed() >= self.tick {
+                let steps = if self.turbo { TURBO_STEPS } else { 1 };
+                for _ in 0..steps {
+                    self.step_active();
+                }
                 self.last_tick = Instant::now();
             }
             terminal.draw(|frame| self.ui(frame))?;
@@ -168,7 +519,7 @@ impl App {
     fn handle_events(&mut self) -> io::Result<()> {
         match event::read()? {
             Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                self.handle_key_event(key_event.code)
+                self.handle_key_event(key_event.code, key_event.modifiers)
             }
             Event::Resize(width, height) => {
                 self.size = (width, height);
@@ -178,9 +529,20 @@ impl App {
         Ok(())
     }
 
-    fn handle_key_event(&mut self, code: KeyCode) {
+    fn handle_key_event(&mut self, code: KeyCode, modifiers: KeyModifiers) {
         let mut moved = false;
 
+        // Any key other than space ends the current paint stroke so it
+        // can be undone as a single operation.
+        if code != KeyCode::Char(' ') {
+            self.undo_stack.end_stroke();
+        }
+
+        if modifiers.contains(KeyModifiers::CONTROL) && code == KeyCode::Char('r') {
+            self.redo();
+            return;
+        }
+
         match code {
             KeyCode::Char('q') => self.exit = true,
             KeyCode::Left => {
@@ -199,13 +561,58 @@ impl App {
                 self.cursor_y = (self.cursor_y + 1).min(self.grid.h - 1);
                 moved = true
             }
-            KeyCode::Char(' ') => self.grid.toggle(self.cursor_x, self.cursor_y),
-            KeyCode::Char('c') => self.grid.clear(),
-            KeyCode::Char('r') => self.regen_random(RANDCHANCE),
+            KeyCode::Char(' ') => {
+                let (x, y) = (self.cursor_x, self.cursor_y);
+                let old = self.grid.get(x, y).unwrap_or(false);
+                self.grid.toggle(x, y);
+                self.undo_stack.record_paint(x, y, old, !old);
+            }
+            KeyCode::Char('c') => {
+                let before = self.grid.cells.clone();
+                self.grid.clear();
+                self.commit_snapshot(OpKind::Fill, before);
+            }
+            KeyCode::Char('r') => {
+                let before = self.grid.cells.clone();
+                self.regen_random(RANDCHANCE);
+                self.commit_snapshot(OpKind::Generate, before);
+            }
             KeyCode::Char('n') => {
                 self.seed += 1;
+                let before = self.grid.cells.clone();
                 self.regen_random(RANDCHANCE);
+                self.commit_snapshot(OpKind::Generate, before);
+            }
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('k') => self.connect_regions(),
+            KeyCode::Char('+') => {
+                let ms = self.tick.as_millis() as u64;
+                self.tick = Duration::from_millis(ms.saturating_sub(TICK_STEP_MS).max(MIN_TICK_MS));
             }
+            KeyCode::Char('-') => {
+                let ms = self.tick.as_millis() as u64;
+                self.tick = Duration::from_millis((ms + TICK_STEP_MS).min(MAX_TICK_MS));
+            }
+            KeyCode::Char('t') => self.turbo = !self.turbo,
+            KeyCode::Char('w') => self.save_map(),
+            KeyCode::Char('l') => self.load_map(),
+            KeyCode::Char('v') => {
+                if self.selecting {
+                    self.selecting = false;
+                } else {
+                    self.selecting = true;
+                    self.selection =
+                        Some((self.cursor_x, self.cursor_y, self.cursor_x, self.cursor_y));
+                }
+            }
+            KeyCode::Esc => {
+                self.selecting = false;
+                self.selection = None;
+            }
+            KeyCode::Char('f') => self.fill_selection(true),
+            KeyCode::Char('d') => self.fill_selection(false),
+            KeyCode::Char('y') => self.yank_selection(),
+            KeyCode::Char('b') => self.paste_clipboard(),
             KeyCode::Char('p') => {
                 self.running = !self.running;
                 self.last_tick = Instant::now();
@@ -224,10 +631,25 @@ impl App {
                 self.running = false;
                 self.last_tick = Instant::now();
             }
+            KeyCode::Char('4') => {
+                self.algo = Algorithm::Smooth;
+                self.running = false;
+                self.last_tick = Instant::now();
+                // Reroll the seed noise each press, like `n`, so repeatedly
+                // hitting `4` while already in Smooth is a useful reroll
+                // rather than a silent no-op that reproduces the same noise.
+                self.seed += 1;
+                let before = self.grid.cells.clone();
+                self.regen_random(SMOOTHCHANCE);
+                self.commit_snapshot(OpKind::Generate, before);
+            }
 
             _ => {}
         }
         if moved {
+            if let Some((ax, ay, _, _)) = self.selection.filter(|_| self.selecting) {
+                self.selection = Some((ax, ay, self.cursor_x, self.cursor_y));
+            }
             self.follow_cursor();
         }
     }
@@ -243,6 +665,28 @@ impl App {
             "<2>".into(),
             " DrunkWalk ".into(),
             "<3>".into(),
+            " Smooth ".into(),
+            "<4>".into(),
+            " Undo ".into(),
+            "<U>".into(),
+            " Redo ".into(),
+            "<^R>".into(),
+            " Connect ".into(),
+            "<K>".into(),
+            " Speed ".into(),
+            "<-/+>".into(),
+            " Turbo ".into(),
+            "<T>".into(),
+            " Save ".into(),
+            "<W>".into(),
+            " Load ".into(),
+            "<L>".into(),
+            " Select ".into(),
+            "<V>".into(),
+            " Fill/Clear ".into(),
+            "<F/D>".into(),
+            " Yank/Paste ".into(),
+            "<Y/B>".into(),
             " Quit ".into(),
             "<Q>".blue().bold(),
         ]);
@@ -266,20 +710,46 @@ impl App {
         self.follow_cursor();
 
         let debug_text = Text::from(vec![Line::from(vec![
-            " Cursor Position ".red().into(),
+            " Cursor Position ".red(),
             format!("x: {} y: {}", self.cursor_x, self.cursor_y).into(),
-            " Seed ".red().into(),
+            " Seed ".red(),
             self.seed.to_string().into(),
-            " Algo: ".red().into(),
+            " Algo: ".red(),
             format!("{:?}", self.algo).into(),
-            " Inner ".red().into(),
+            " Inner ".red(),
             format!("{}x{}", inner.width, inner.height).into(),
-            " View ".red().into(),
+            " View ".red(),
             format!("{}x{}", self.view_w, self.view_h).into(),
-            " World ".red().into(),
+            " World ".red(),
             format!("{}x{}", self.grid.w, self.grid.h).into(),
-            " Running ".red().into(),
+            " Running ".red(),
             self.running.to_string().into(),
+            " Regions ".red(),
+            self.region_count.to_string().into(),
+            " Largest ".red(),
+            self.largest_region.to_string().into(),
+            " Last Op ".red(),
+            self.undo_stack
+                .last_kind()
+                .map_or("-".to_string(), |k| format!("{k:?}"))
+                .into(),
+            " Tick ".red(),
+            format!("{}ms", self.tick.as_millis()).into(),
+            " Turbo ".red(),
+            self.turbo.to_string().into(),
+            " Status ".red(),
+            self.status.clone().into(),
+            " Selection ".red(),
+            self.normalized_selection()
+                .map_or("-".to_string(), |(x0, y0, x1, y1)| {
+                    format!("{}x{}", x1 - x0 + 1, y1 - y0 + 1)
+                })
+                .into(),
+            " Clipboard ".red(),
+            self.clipboard
+                .as_ref()
+                .map_or("-".to_string(), |(w, h, _)| format!("{w}x{h}"))
+                .into(),
         ])]);
 
         frame.render_widget(
@@ -292,6 +762,8 @@ impl App {
         let end_x = (start_x + self.view_w).min(self.grid.w);
         let end_y = (start_y + self.view_h).min(self.grid.h);
 
+        let selection = self.normalized_selection();
+
         // Grid
         let mut rows: Vec<Line> = Vec::with_capacity(end_y - start_y);
         for y in start_y..end_y {
@@ -301,8 +773,13 @@ impl App {
                 let filled = self.grid.get(x, y).unwrap_or(false);
                 let cell = if filled { "██" } else { "  " };
 
+                let in_selection = selection
+                    .is_some_and(|(x0, y0, x1, y1)| x >= x0 && x <= x1 && y >= y0 && y <= y1);
+
                 let span = if x == self.cursor_x && y == self.cursor_y {
                     Span::from(cell).reversed()
+                } else if in_selection {
+                    Span::from(cell).reversed().yellow()
                 } else {
                     Span::from(cell)
                 };
@@ -326,12 +803,17 @@ impl App {
             Algorithm::DrunkWalk => {
                 // Increment seed to chance cave every step
                 self.seed += 1;
-                self.gen_drunk_walk(DRUNKCHANCE);
+                let before = self.grid.cells.clone();
+                self.gen_drunk_walk(DRUNK_AGENTS, DRUNKCHANCE);
+                self.commit_snapshot(OpKind::Generate, before);
                 // Move viewport to the center of the grid
                 self.cursor_x = self.grid.w / 2 + self.view_w / 2;
                 self.cursor_y = self.grid.h / 2 + self.view_h / 2;
                 self.follow_cursor();
             }
+            Algorithm::Smooth => {
+                self.grid.step_smooth();
+            }
         }
     }
 
@@ -368,6 +850,229 @@ impl App {
         }
     }
 
+    /// Returns the active selection as `(min_x, min_y, max_x, max_y)`,
+    /// regardless of which corner is the anchor and which is the cursor.
+    fn normalized_selection(&self) -> Option<(usize, usize, usize, usize)> {
+        self.selection
+            .map(|(x0, y0, x1, y1)| (x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)))
+    }
+
+    /// Fills the active selection rectangle with walls (`true`) or opens
+    /// it (`false`), pushing the change as a single undoable `Operation`.
+    fn fill_selection(&mut self, wall: bool) {
+        let Some((x0, y0, x1, y1)) = self.normalized_selection() else {
+            return;
+        };
+
+        let before = self.grid.cells.clone();
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                self.grid.set(x, y, wall);
+            }
+        }
+        self.commit_snapshot(OpKind::Fill, before);
+    }
+
+    /// Copies the active selection rectangle into the clipboard.
+    fn yank_selection(&mut self) {
+        let Some((x0, y0, x1, y1)) = self.normalized_selection() else {
+            return;
+        };
+
+        let w = x1 - x0 + 1;
+        let h = y1 - y0 + 1;
+        let mut buf = Vec::with_capacity(w * h);
+        for y in y0..=y1 {
+            for x in x0..=x1 {
+                buf.push(self.grid.get(x, y).unwrap_or(false));
+            }
+        }
+
+        self.clipboard = Some((w, h, buf));
+        self.status = format!("Yanked {w}x{h}");
+    }
+
+    /// Stamps the clipboard at the cursor, clipped to the grid bounds.
+    fn paste_clipboard(&mut self) {
+        let Some((w, h, buf)) = self.clipboard.clone() else {
+            return;
+        };
+
+        let before = self.grid.cells.clone();
+        for dy in 0..h {
+            for dx in 0..w {
+                let x = self.cursor_x + dx;
+                let y = self.cursor_y + dy;
+                if x < self.grid.w && y < self.grid.h {
+                    self.grid.set(x, y, buf[dy * w + dx]);
+                }
+            }
+        }
+        self.commit_snapshot(OpKind::Paint, before);
+    }
+
+    /// Serializes the current grid, seed, and algorithm to `SAVE_PATH` as
+    /// JSON, reporting success/failure in `status` for the debug panel.
+    fn save_map(&mut self) {
+        let save = SaveFile {
+            w: self.grid.w,
+            h: self.grid.h,
+            seed: self.seed,
+            algo: algo_to_str(&self.algo),
+            cells: encode_cells(&self.grid.cells),
+        };
+
+        self.status = match serde_json::to_string_pretty(&save) {
+            Ok(json) => match fs::write(SAVE_PATH, json) {
+                Ok(()) => format!("Saved to {SAVE_PATH}"),
+                Err(e) => format!("Save failed: {e}"),
+            },
+            Err(e) => format!("Save failed: {e}"),
+        };
+    }
+
+    /// Loads a `SaveFile` from `SAVE_PATH`, validates its cell count
+    /// against its declared dimensions, and rebuilds the grid, seed, and
+    /// algorithm from it, re-centering the viewport on the loaded map.
+    fn load_map(&mut self) {
+        let json = match fs::read_to_string(SAVE_PATH) {
+            Ok(json) => json,
+            Err(e) => {
+                self.status = format!("Load failed: {e}");
+                return;
+            }
+        };
+
+        let save: SaveFile = match serde_json::from_str(&json) {
+            Ok(save) => save,
+            Err(e) => {
+                self.status = format!("Load failed: {e}");
+                return;
+            }
+        };
+
+        if save.w < MIN_MAP_DIM || save.h < MIN_MAP_DIM {
+            self.status = format!(
+                "Load failed: {}x{} is smaller than the {MIN_MAP_DIM}x{MIN_MAP_DIM} minimum",
+                save.w, save.h
+            );
+            return;
+        }
+
+        let cells = match decode_cells(&save.cells, save.w * save.h) {
+            Some(cells) => cells,
+            None => {
+                self.status = "Load failed: cell data does not match declared dimensions".into();
+                return;
+            }
+        };
+
+        self.grid = Grid::new(save.w, save.h);
+        self.grid.cells = cells;
+        self.seed = save.seed;
+        self.algo = algo_from_str(&save.algo).unwrap_or(Algorithm::Paint);
+        self.undo_stack = UndoStack::default();
+        // A selection/clipboard from the previous grid can reference
+        // coordinates or dimensions that no longer make sense here.
+        self.selecting = false;
+        self.selection = None;
+        self.clipboard = None;
+
+        self.cursor_x = self.cursor_x.min(self.grid.w.saturating_sub(1));
+        self.cursor_y = self.cursor_y.min(self.grid.h.saturating_sub(1));
+        self.follow_cursor();
+
+        self.status = format!("Loaded {}x{} from {SAVE_PATH}", save.w, save.h);
+    }
+
+    /// Diffs `self.grid.cells` against a `before` snapshot and pushes the
+    /// changed cells as a single `Operation` of the given kind.
+    fn commit_snapshot(&mut self, kind: OpKind, before: Vec<bool>) {
+        let mut records = Vec::new();
+        for (i, &old) in before.iter().enumerate() {
+            let new = self.grid.cells[i];
+            if old != new {
+                records.push(ModifyRecord {
+                    x: i % self.grid.w,
+                    y: i / self.grid.w,
+                    old,
+                    new,
+                });
+            }
+        }
+        self.undo_stack.push(Operation { kind, records });
+    }
+
+    /// Labels the cave's open-cell regions and, if more than one exists,
+    /// carves L-shaped tunnels so the whole cave becomes one connected
+    /// region. Above `REGION_CONNECT_THRESHOLD` regions no tunnels are
+    /// carved; every region but the largest is walled off instead.
+    /// Updates `region_count`/`largest_region` for the debug panel.
+    fn connect_regions(&mut self) {
+        let before = self.grid.cells.clone();
+        let (labels, regions) = self.grid.label_regions();
+
+        self.region_count = regions.len();
+        self.largest_region = regions.iter().map(|r| r.count).max().unwrap_or(0);
+
+        if regions.len() > REGION_CONNECT_THRESHOLD {
+            // Too many regions for tunnel carving to stay an instant
+            // action; fall back to keeping only the largest one.
+            self.grid.keep_largest_region(&labels, &regions);
+        } else if regions.len() > 1 {
+            // Prim's MST over region representatives: grow a connected
+            // set, tracking each unconnected region's nearest connected
+            // neighbor so each new connection only updates distances
+            // against the region just added, not a full rescan.
+            let mut connected = vec![false; regions.len()];
+            connected[0] = true;
+            let mut best_dist = vec![usize::MAX; regions.len()];
+            let mut best_from = vec![0usize; regions.len()];
+            for (i, region) in regions.iter().enumerate().skip(1) {
+                best_dist[i] = manhattan(regions[0].rep, region.rep);
+            }
+
+            for _ in 1..regions.len() {
+                let to = (0..regions.len())
+                    .filter(|&i| !connected[i])
+                    .min_by_key(|&i| best_dist[i])
+                    .expect("at least one region remains unconnected");
+
+                let from = best_from[to];
+                self.grid.carve_l_tunnel(regions[from].rep, regions[to].rep);
+                connected[to] = true;
+
+                for (i, region) in regions.iter().enumerate() {
+                    if !connected[i] {
+                        let d = manhattan(regions[to].rep, region.rep);
+                        if d < best_dist[i] {
+                            best_dist[i] = d;
+                            best_from[i] = to;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.commit_snapshot(OpKind::Generate, before);
+    }
+
+    fn undo(&mut self) {
+        if let Some(op) = self.undo_stack.undo() {
+            for record in op.records.iter().rev() {
+                self.grid.set(record.x, record.y, record.old);
+            }
+        }
+    }
+
+    fn redo(&mut self) {
+        if let Some(op) = self.undo_stack.redo() {
+            for record in &op.records {
+                self.grid.set(record.x, record.y, record.new);
+            }
+        }
+    }
+
     fn regen_random(&mut self, p: f64) {
         // clear grid before generation
         self.grid.clear();
@@ -384,54 +1089,111 @@ impl App {
             }
         }
     }
-    fn gen_drunk_walk(&mut self, carve_target_ratio: f64) {
+    /// Multi-agent drunk-walk carver: spawns `agents` independent walkers
+    /// from scattered seed positions, each with its own `StdRng` derived
+    /// from `seed.wrapping_add(agent_index)`. A shared `heat` buffer
+    /// counts visits per cell, and each walker biases its next step away
+    /// from high-heat cells so the agents spread into branching tunnels
+    /// rather than overlapping into one blob.
+    fn gen_drunk_walk(&mut self, agents: usize, carve_target_ratio: f64) {
         // start as solid walls
         self.grid.fill(true);
 
-        // seeded rng
-        let mut rng = StdRng::seed_from_u64(self.seed);
-
-        // start in the center
-        let mut x = self.grid.w / 2;
-        let mut y = self.grid.h / 2;
-
-        // keep a 1-cell border as walls to avoid open edges
-        x = x.clamp(1, self.grid.w.saturating_sub(2));
-        y = y.clamp(1, self.grid.h.saturating_sub(2));
+        // Walkers can only ever reach the interior inside the 1-cell
+        // border, so the carve target must be capped there too — on a
+        // small enough grid the ratio-based target can exceed every
+        // reachable cell, which would otherwise spin forever.
+        let interior_w = self.grid.w.saturating_sub(2);
+        let interior_h = self.grid.h.saturating_sub(2);
+        if interior_w == 0 || interior_h == 0 {
+            return;
+        }
 
-        // decide how much to carve
         let total = self.grid.w * self.grid.h;
-        let target_open = ((total as f64) * carve_target_ratio).round() as usize;
+        let reachable = interior_w * interior_h;
+        let target_open = (((total as f64) * carve_target_ratio).round() as usize).min(reachable);
+        let mut heat = vec![0u16; total];
 
-        // carve until we hit target
-        let mut opened = 0usize;
+        let mut walkers: Vec<(StdRng, usize, usize)> = (0..agents.max(1))
+            .map(|i| {
+                let rng = StdRng::seed_from_u64(self.seed.wrapping_add(i as u64));
+                let (x, y) = Self::scatter_seed(self.grid.w, self.grid.h, i, agents.max(1));
+                (rng, x, y)
+            })
+            .collect();
 
-        // carve starting cell
-        if self.grid.get(x, y) == Some(true) {
-            self.grid.set(x, y, false);
-            opened += 1;
+        let mut opened = 0usize;
+        for (_, x, y) in &walkers {
+            let i = self.grid.idx(*x, *y);
+            heat[i] += 1;
+            if self.grid.cells[i] {
+                self.grid.cells[i] = false;
+                opened += 1;
+            }
         }
 
+        let mut turn = 0usize;
         while opened < target_open {
-            // choose direction 0..4
-            match rng.random_range(0..4) {
-                0 => x = x.saturating_sub(1),
-                1 => x = (x + 1).min(self.grid.w - 1),
-                2 => y = y.saturating_sub(1),
-                _ => y = (y + 1).min(self.grid.h - 1),
+            let idx = turn % walkers.len();
+            let (rng, x, y) = &mut walkers[idx];
+
+            let candidates = [
+                (x.saturating_sub(1), *y),
+                ((*x + 1).min(self.grid.w - 1), *y),
+                (*x, y.saturating_sub(1)),
+                (*x, (*y + 1).min(self.grid.h - 1)),
+            ];
+            let weights: Vec<f64> = candidates
+                .iter()
+                .map(|&(cx, cy)| 1.0 / (1.0 + heat[self.grid.idx(cx, cy)] as f64))
+                .collect();
+            let total_weight: f64 = weights.iter().sum();
+
+            let mut pick = rng.random_range(0.0..total_weight);
+            let mut next = candidates[candidates.len() - 1];
+            for (&cand, &w) in candidates.iter().zip(weights.iter()) {
+                if pick < w {
+                    next = cand;
+                    break;
+                }
+                pick -= w;
             }
 
-            // enforce border walls
-            x = x.clamp(1, self.grid.w.saturating_sub(2));
-            y = y.clamp(1, self.grid.h.saturating_sub(2));
+            // keep a 1-cell border as walls to avoid open edges
+            *x = next.0.clamp(1, self.grid.w.saturating_sub(2));
+            *y = next.1.clamp(1, self.grid.h.saturating_sub(2));
 
-            // carve if still wall
-            if self.grid.get(x, y) == Some(true) {
-                self.grid.set(x, y, false);
+            let i = self.grid.idx(*x, *y);
+            heat[i] += 1;
+            if self.grid.cells[i] {
+                self.grid.cells[i] = false;
                 opened += 1;
             }
+
+            turn += 1;
         }
     }
+
+    /// Spreads walker `i` of `n` around the grid center on a circle, so
+    /// multiple agents start from distinct, scattered positions.
+    fn scatter_seed(w: usize, h: usize, i: usize, n: usize) -> (usize, usize) {
+        let cx = w / 2;
+        let cy = h / 2;
+        if n <= 1 {
+            return (cx, cy);
+        }
+
+        let angle = i as f64 * std::f64::consts::TAU / n as f64;
+        let radius_x = (w as f64 / 4.0).max(1.0);
+        let radius_y = (h as f64 / 4.0).max(1.0);
+
+        let x = (cx as f64 + angle.cos() * radius_x).round();
+        let y = (cy as f64 + angle.sin() * radius_y).round();
+
+        let x = (x as isize).clamp(1, w.saturating_sub(2) as isize) as usize;
+        let y = (y as isize).clamp(1, h.saturating_sub(2) as isize) as usize;
+        (x, y)
+    }
 }
 
 impl Default for App {
@@ -450,6 +1212,58 @@ impl Default for App {
             algo: Algorithm::Paint,
             last_tick: Instant::now(),
             running: false,
+            undo_stack: UndoStack::default(),
+            region_count: 0,
+            largest_region: 0,
+            tick: TICK,
+            turbo: false,
+            status: String::new(),
+            selecting: false,
+            selection: None,
+            clipboard: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_cells_round_trip() {
+        let cells = vec![
+            true, true, true, false, false, true, false, false, false, false,
+        ];
+        let encoded = encode_cells(&cells);
+        let decoded = decode_cells(&encoded, cells.len()).expect("round trip should decode");
+        assert_eq!(decoded, cells);
+    }
+
+    #[test]
+    fn decode_cells_rejects_length_mismatch() {
+        let encoded = encode_cells(&[true, false, true]);
+        assert!(decode_cells(&encoded, 4).is_none());
+    }
+
+    #[test]
+    fn gen_drunk_walk_terminates_on_small_grid() {
+        let mut app = App {
+            grid: Grid::new(5, 5),
+            ..App::default()
+        };
+        // Would previously spin forever: the 0.4 ratio asks for more open
+        // cells than a 5x5 grid's 3x3 walkable interior can ever hold.
+        app.gen_drunk_walk(4, 0.4);
+        assert_eq!(app.grid.cells.len(), 25);
+    }
+
+    #[test]
+    fn gen_drunk_walk_terminates_on_minimum_grid() {
+        let mut app = App {
+            grid: Grid::new(MIN_MAP_DIM, MIN_MAP_DIM),
+            ..App::default()
+        };
+        app.gen_drunk_walk(4, 0.4);
+        assert_eq!(app.grid.cells.len(), MIN_MAP_DIM * MIN_MAP_DIM);
+    }
+}